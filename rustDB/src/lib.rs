@@ -1,9 +1,22 @@
+// The crate name matches the `rustDB/` directory it lives in (and the
+// `use rustDB::...` imports throughout this workspace), not Rust's usual
+// snake_case convention.
+#![allow(non_snake_case)]
+
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
 use std::io::{self, Write};
 use std::path::PathBuf;
 
+#[cfg(feature = "server")]
+pub mod server;
+
+/// A single stored document. Collections are untyped, so documents are
+/// just arbitrary JSON values (usually objects).
+pub type Document = serde_json::Value;
+
 // Custom error type
 #[derive(Debug)]
 pub enum RustDbError {
@@ -11,6 +24,9 @@ pub enum RustDbError {
     IoError(io::Error),
     SerializationError(serde_json::Error),
     DeserializationError(serde_json::Error),
+    RegexError(regex::Error),
+    NotUnique { collection: String, field: String },
+    CsvError(csv::Error),
 }
 
 impl From<io::Error> for RustDbError {
@@ -29,12 +45,119 @@ impl From<serde_json::Error> for RustDbError {
     }
 }
 
-// Database Struct
+impl From<regex::Error> for RustDbError {
+    fn from(err: regex::Error) -> Self {
+        RustDbError::RegexError(err)
+    }
+}
+
+impl From<csv::Error> for RustDbError {
+    fn from(err: csv::Error) -> Self {
+        RustDbError::CsvError(err)
+    }
+}
+
+impl std::fmt::Display for RustDbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RustDbError::KeyNotFound => write!(f, "key not found"),
+            RustDbError::IoError(err) => write!(f, "I/O error: {}", err),
+            RustDbError::SerializationError(err) => {
+                write!(f, "failed to serialize document: {}", err)
+            }
+            RustDbError::DeserializationError(err) => {
+                write!(f, "failed to deserialize document: {}", err)
+            }
+            RustDbError::RegexError(err) => write!(f, "invalid regex pattern: {}", err),
+            RustDbError::NotUnique { collection, field } => write!(
+                f,
+                "value for unique field '{}' already exists in collection '{}'",
+                field, collection
+            ),
+            RustDbError::CsvError(err) => write!(f, "CSV error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RustDbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RustDbError::IoError(err) => Some(err),
+            RustDbError::SerializationError(err) | RustDbError::DeserializationError(err) => {
+                Some(err)
+            }
+            RustDbError::RegexError(err) => Some(err),
+            RustDbError::CsvError(err) => Some(err),
+            RustDbError::KeyNotFound | RustDbError::NotUnique { .. } => None,
+        }
+    }
+}
+
+type Collection = HashMap<String, Document>;
+
+/// A single write-ahead-log record. Each record is written as one JSON
+/// line followed by a trailing checksum, so a torn write at the end of the
+/// file (e.g. a crash mid-`writeln!`) is detected and discarded on replay
+/// instead of corrupting the document it touched.
 #[derive(Debug, Serialize, Deserialize)]
+struct WalRecord {
+    seq: u64,
+    #[serde(flatten)]
+    op: WalOp,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum WalOp {
+    Insert {
+        collection: String,
+        id: String,
+        value: Document,
+    },
+    Delete {
+        collection: String,
+        id: String,
+    },
+}
+
+/// A small FNV-1a hash used as a per-record integrity check; this is not a
+/// cryptographic checksum, just enough to detect a torn final write.
+fn checksum(data: &str) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in data.bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+/// On-disk shape of `file_path`: the collections plus whatever unique
+/// indexes have been registered, so they are written out by every `save`
+/// and reloaded on restart. `create_unique_index` itself does not save: an
+/// index registered after the last write is not on disk until the next one.
+#[derive(Debug, Default, Deserialize)]
+struct Snapshot {
+    collections: HashMap<String, Collection>,
+    #[serde(default)]
+    unique_indexes: HashMap<String, Vec<String>>,
+}
+
+/// Borrowed mirror of `Snapshot` used to serialize a save without cloning
+/// the whole in-memory state first.
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    collections: &'a HashMap<String, Collection>,
+    unique_indexes: &'a HashMap<String, Vec<String>>,
+}
+
+// Database Struct
+#[derive(Debug)]
 pub struct RustDb {
-    data: HashMap<String, String>,
+    collections: HashMap<String, Collection>,
+    unique_indexes: HashMap<String, Vec<String>>,
     file_path: PathBuf,
     log_path: PathBuf,
+    next_seq: u64,
 }
 
 // Implement methods
@@ -43,15 +166,20 @@ impl RustDb {
     pub fn new(file_path: &str) -> Result<Self, RustDbError> {
         let path = PathBuf::from(file_path);
         let mut db = RustDb {
-            data: HashMap::new(),
+            collections: HashMap::new(),
+            unique_indexes: HashMap::new(),
             file_path: path.clone(),
             log_path: path.with_extension("wal"),
+            next_seq: 0,
         };
         match db.load() {
             Ok(_) => {
-                // replay any remaining logs
+                // Replay any remaining log entries, then checkpoint the
+                // recovered state to disk before truncating the WAL - if we
+                // cleared the log without saving first, a second crash
+                // before the next write would lose the recovered data.
                 db.replay_log()?;
-                db.clear_log()?;
+                db.save()?;
                 Ok(db)
             }
             Err(RustDbError::IoError(ref err)) if err.kind() == io::ErrorKind::NotFound => {
@@ -67,15 +195,37 @@ impl RustDb {
 
     fn load(&mut self) -> Result<(), RustDbError> {
         let content = fs::read_to_string(&self.file_path)?;
-        if content.trim().is_empty() {
-            self.data = HashMap::new();
+        let snapshot: Snapshot = if content.trim().is_empty() {
+            Snapshot::default()
         } else {
-            self.data = serde_json::from_str(&content)?;
-        }
+            // Database files saved before unique indexes existed store a bare
+            // `{collection: {...}}` map at the top level instead of this
+            // wrapper; fall back to reading that shape so upgrading doesn't
+            // strand a user's existing data.
+            serde_json::from_str(&content).or_else(|_| {
+                serde_json::from_str::<HashMap<String, Collection>>(&content).map(|collections| {
+                    Snapshot {
+                        collections,
+                        unique_indexes: HashMap::new(),
+                    }
+                })
+            })?
+        };
+        self.collections = snapshot.collections;
+        self.unique_indexes = snapshot.unique_indexes;
         Ok(())
     }
 
-    fn write_log(&self, entry: &str) -> Result<(), RustDbError> {
+    fn write_log(&mut self, op: WalOp) -> Result<(), RustDbError> {
+        let record = WalRecord {
+            seq: self.next_seq,
+            op,
+        };
+        self.next_seq += 1;
+
+        let json = serde_json::to_string(&record)?;
+        let line = format!("{} {}", json, checksum(&json));
+
         // create a file "object"
         let mut file = OpenOptions::new()
             .create(true)
@@ -83,52 +233,136 @@ impl RustDb {
             .open(&self.log_path)?;
 
         // append to the file the log_entry
-        writeln!(file, "{}", entry)?;
+        writeln!(file, "{}", line)?;
         Ok(())
     }
 
-    pub fn insert(&mut self, key: String, value: String) -> Result<(), RustDbError> {
-        self.write_log(&format!("insert {} {}", key, value))?;
-        self.data.insert(key, value);
+    /// Records that `field` must be unique within `collection`; subsequent
+    /// `insert_document` calls that would duplicate an existing value for
+    /// that field are rejected with `NotUnique`.
+    pub fn create_unique_index(&mut self, collection: &str, field: &str) {
+        let fields = self
+            .unique_indexes
+            .entry(collection.to_string())
+            .or_default();
+        if !fields.iter().any(|f| f == field) {
+            fields.push(field.to_string());
+        }
+    }
+
+    pub fn insert_document(
+        &mut self,
+        collection: String,
+        id: String,
+        document: Document,
+    ) -> Result<(), RustDbError> {
+        if let Some(fields) = self.unique_indexes.get(&collection) {
+            if let Some(docs) = self.collections.get(&collection) {
+                for field in fields {
+                    if let Some(new_value) = resolve_field(&document, field) {
+                        let duplicate = docs.iter().any(|(existing_id, existing_doc)| {
+                            existing_id != &id
+                                && resolve_field(existing_doc, field) == Some(new_value)
+                        });
+                        if duplicate {
+                            return Err(RustDbError::NotUnique {
+                                collection,
+                                field: field.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        self.write_log(WalOp::Insert {
+            collection: collection.clone(),
+            id: id.clone(),
+            value: document.clone(),
+        })?;
+        self.collections
+            .entry(collection)
+            .or_default()
+            .insert(id, document);
         self.save()?;
         Ok(())
     }
 
-    pub fn save(&self) -> Result<(), RustDbError> {
-        let serialized = serde_json::to_string_pretty(&self.data)?;
-        fs::write(&self.file_path, serialized.as_bytes())?;
+    /// Writes the full in-memory state to `file_path` and checkpoints the
+    /// WAL. The snapshot is written to a temp file and atomically renamed
+    /// into place so a crash mid-write can never leave a half-written
+    /// database file; the WAL is only truncated once that rename succeeds.
+    pub fn save(&mut self) -> Result<(), RustDbError> {
+        let snapshot = SnapshotRef {
+            collections: &self.collections,
+            unique_indexes: &self.unique_indexes,
+        };
+        let serialized = serde_json::to_string_pretty(&snapshot)?;
+        let tmp_path = self.file_path.with_extension("tmp");
+        fs::write(&tmp_path, serialized.as_bytes())?;
+        fs::rename(&tmp_path, &self.file_path)?;
+        self.clear_log()?;
         Ok(())
     }
 
-    pub fn get(&self, key: &str) -> Option<&String> {
-        self.data.get(key)
+    pub fn get_document(&self, collection: &str, id: &str) -> Option<&Document> {
+        self.collections.get(collection)?.get(id)
     }
 
-    pub fn delete(&mut self, key: &str) -> Result<(), RustDbError> {
-        self.write_log(&format!("delete {}", key))?;
-        if self.data.remove(key).is_some() {
-            self.save()?;
-            Ok(())
-        } else {
-            Err(RustDbError::KeyNotFound)
+    pub fn delete_document(&mut self, collection: &str, id: &str) -> Result<(), RustDbError> {
+        self.write_log(WalOp::Delete {
+            collection: collection.to_string(),
+            id: id.to_string(),
+        })?;
+        if let Some(coll) = self.collections.get_mut(collection) {
+            if coll.remove(id).is_some() {
+                self.save()?;
+                return Ok(());
+            }
         }
+        Err(RustDbError::KeyNotFound)
     }
 
     fn replay_log(&mut self) -> Result<(), RustDbError> {
         if let Ok(content) = fs::read_to_string(&self.log_path) {
+            let mut max_seq = None;
             for line in content.lines() {
-                let parts: Vec<&str> = line.splitn(3, ' ').collect();
+                // A torn final write (missing checksum, invalid JSON, or a
+                // checksum mismatch) means the process crashed mid-append;
+                // stop replaying rather than risk applying a partial record.
+                let Some((json, checksum_str)) = line.rsplit_once(' ') else {
+                    break;
+                };
+                let Ok(expected) = checksum_str.parse::<u32>() else {
+                    break;
+                };
+                if checksum(json) != expected {
+                    break;
+                }
+                let Ok(record) = serde_json::from_str::<WalRecord>(json) else {
+                    break;
+                };
 
-                match parts.as_slice() {
-                    ["insert", key, value] => {
-                        self.data.insert(key.to_string(), value.to_string());
+                max_seq = Some(max_seq.map_or(record.seq, |m: u64| m.max(record.seq)));
+                match record.op {
+                    WalOp::Insert {
+                        collection,
+                        id,
+                        value,
+                    } => {
+                        self.collections
+                            .entry(collection)
+                            .or_default()
+                            .insert(id, value);
                     }
-                    ["delete", key] => {
-                        self.data.remove(*key);
+                    WalOp::Delete { collection, id } => {
+                        if let Some(coll) = self.collections.get_mut(&collection) {
+                            coll.remove(&id);
+                        }
                     }
-                    _ => {}
                 }
             }
+            self.next_seq = max_seq.map_or(0, |seq| seq + 1);
         }
         Ok(())
     }
@@ -138,14 +372,311 @@ impl RustDb {
         Ok(())
     }
 
-    pub fn list_all(&self) -> Vec<(String, String)> {
-        self.data
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect()
+    pub fn list_collection_documents(&self, collection: &str) -> Vec<(String, Document)> {
+        self.collections
+            .get(collection)
+            .map(|docs| docs.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear_collections(&mut self, collection: &str) -> Result<(), RustDbError> {
+        self.collections.remove(collection);
+        self.save()?;
+        Ok(())
+    }
+
+    /// Finds documents in `collection` whose value at `field_path` (a
+    /// dot-separated path like `address.city`) satisfies `predicate`.
+    ///
+    /// `predicate` is one of:
+    ///   - a JSON literal for equality, e.g. `30` or `"Alice"`
+    ///   - `<N`, `>N`, `<=N`, `>=N` for numeric comparison
+    ///   - `~PATTERN` to match the stringified field value against a regex
+    pub fn find(
+        &self,
+        collection: &str,
+        field_path: &str,
+        predicate: &str,
+    ) -> Result<Vec<(String, Document)>, RustDbError> {
+        let mut matches = Vec::new();
+        let Some(docs) = self.collections.get(collection) else {
+            return Ok(matches);
+        };
+        for (id, document) in docs {
+            if let Some(field_value) = resolve_field(document, field_path) {
+                if matches_predicate(field_value, predicate)? {
+                    matches.push((id.clone(), document.clone()));
+                }
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Imports `path` into `collection`. A `.csv` extension is read as CSV
+    /// (the header row names the document fields); anything else is read as
+    /// a JSON array of objects. Each document is keyed by `id_field` when
+    /// given (falling back to its row/array position if the field is
+    /// missing), otherwise by its row/array position.
+    pub fn import_collection(
+        &mut self,
+        collection: &str,
+        path: &str,
+        id_field: Option<&str>,
+    ) -> Result<usize, RustDbError> {
+        let documents = if path.ends_with(".csv") {
+            let mut reader = csv::Reader::from_path(path).map_err(RustDbError::from)?;
+            let headers = reader.headers()?.clone();
+            let mut documents = Vec::new();
+            for record in reader.records() {
+                let record = record?;
+                let mut object = serde_json::Map::new();
+                for (header, value) in headers.iter().zip(record.iter()) {
+                    // The id column keeps its literal text (e.g. a zero-padded
+                    // zip code) rather than being numerically inferred, since
+                    // it's about to become the document's id below.
+                    let inferred = if Some(header) == id_field {
+                        Document::String(value.to_string())
+                    } else {
+                        infer_csv_value(value)
+                    };
+                    object.insert(header.to_string(), inferred);
+                }
+                documents.push(Document::Object(object));
+            }
+            documents
+        } else {
+            let content = fs::read_to_string(path)?;
+            serde_json::from_str::<Vec<Document>>(&content)?
+        };
+
+        let count = documents.len();
+        for (index, document) in documents.into_iter().enumerate() {
+            let id = id_field
+                .and_then(|field| document.get(field))
+                .map(|value| match value {
+                    Document::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .unwrap_or_else(|| (index + 1).to_string());
+            self.insert_document(collection.to_string(), id, document)?;
+        }
+        Ok(count)
+    }
+
+    /// Exports `collection` to `path`, as CSV (unioning field names across
+    /// documents) when `path` ends in `.csv`, or as a pretty JSON array
+    /// otherwise.
+    pub fn export_collection(&self, collection: &str, path: &str) -> Result<usize, RustDbError> {
+        let documents = self.list_collection_documents(collection);
+        let count = documents.len();
+
+        if path.ends_with(".csv") {
+            let mut fields: Vec<String> = Vec::new();
+            for (_, document) in &documents {
+                if let Some(object) = document.as_object() {
+                    for key in object.keys() {
+                        if !fields.contains(key) {
+                            fields.push(key.clone());
+                        }
+                    }
+                }
+            }
+
+            let mut writer = csv::Writer::from_path(path).map_err(RustDbError::from)?;
+            writer.write_record(&fields)?;
+            for (_, document) in &documents {
+                let row: Vec<String> = fields
+                    .iter()
+                    .map(|field| match document.get(field) {
+                        Some(Document::String(s)) => s.clone(),
+                        Some(other) => other.to_string(),
+                        None => String::new(),
+                    })
+                    .collect();
+                writer.write_record(&row)?;
+            }
+            writer.flush()?;
+        } else {
+            let values: Vec<Document> = documents.into_iter().map(|(_, doc)| doc).collect();
+            let serialized = serde_json::to_string_pretty(&values)?;
+            fs::write(path, serialized)?;
+        }
+
+        Ok(count)
+    }
+}
+
+/// Infers a JSON type for a raw CSV cell: integers and floats become JSON
+/// numbers and `true`/`false` become booleans, so columns like `age` can
+/// still be matched by `find`'s numeric `<`/`>`/`<=`/`>=` operators, which
+/// only compare against `as_f64()`. Anything else is kept as a string.
+fn infer_csv_value(raw: &str) -> Document {
+    if let Ok(i) = raw.parse::<i64>() {
+        return Document::from(i);
+    }
+    if let Ok(f) = raw.parse::<f64>() {
+        if let Some(number) = serde_json::Number::from_f64(f) {
+            return Document::Number(number);
+        }
+    }
+    if let Ok(b) = raw.parse::<bool>() {
+        return Document::Bool(b);
+    }
+    Document::String(raw.to_string())
+}
+
+/// Walks a dot-notation path (`address.city`) through nested JSON objects.
+fn resolve_field<'a>(document: &'a Document, field_path: &str) -> Option<&'a Document> {
+    let mut current = document;
+    for part in field_path.split('.') {
+        current = current.as_object()?.get(part)?;
     }
+    Some(current)
+}
+
+fn matches_predicate(field_value: &Document, predicate: &str) -> Result<bool, RustDbError> {
+    let predicate = predicate.trim();
+
+    if let Some(pattern) = predicate.strip_prefix('~') {
+        let re = Regex::new(pattern.trim())?;
+        let text = match field_value {
+            Document::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        return Ok(re.is_match(&text));
+    }
+
+    let (op, operand) = if let Some(rest) = predicate.strip_prefix("<=") {
+        ("<=", rest)
+    } else if let Some(rest) = predicate.strip_prefix(">=") {
+        (">=", rest)
+    } else if let Some(rest) = predicate.strip_prefix('<') {
+        ("<", rest)
+    } else if let Some(rest) = predicate.strip_prefix('>') {
+        (">", rest)
+    } else {
+        ("==", predicate)
+    };
+
+    if op == "==" {
+        let literal: Document = serde_json::from_str(operand)?;
+        return Ok(*field_value == literal);
+    }
+
+    let (Some(field_num), Some(operand_num)) =
+        (field_value.as_f64(), operand.trim().parse::<f64>().ok())
+    else {
+        return Ok(false);
+    };
+
+    Ok(match op {
+        "<" => field_num < operand_num,
+        ">" => field_num > operand_num,
+        "<=" => field_num <= operand_num,
+        ">=" => field_num >= operand_num,
+        _ => unreachable!(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unique per-test path under the system temp dir, since tests run
+    /// concurrently and each `RustDb` owns a snapshot + WAL file pair.
+    fn temp_db_path(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("rustdb_test_{}_{}.json", std::process::id(), name));
+        path
+    }
+
+    fn cleanup(path: &PathBuf) {
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(path.with_extension("wal"));
+    }
+
+    #[test]
+    fn replay_log_recovers_committed_entries_and_stops_at_a_torn_write() {
+        let path = temp_db_path("replay_log");
+        cleanup(&path);
+
+        let mut db = RustDb::new(path.to_str().unwrap()).unwrap();
+        db.insert_document(
+            "users".to_string(),
+            "1".to_string(),
+            serde_json::json!({"name": "Alice"}),
+        )
+        .unwrap();
+
+        // `insert_document` always checkpoints via `save()`, which clears the
+        // WAL, so simulate a write that crashed before its checkpoint by
+        // appending straight to the log instead of going through `save()`.
+        db.write_log(WalOp::Insert {
+            collection: "users".to_string(),
+            id: "2".to_string(),
+            value: serde_json::json!({"name": "Bob"}),
+        })
+        .unwrap();
+
+        // A torn final write: well-formed JSON but missing its checksum
+        // suffix, as if the process crashed mid-`writeln!`.
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(&db.log_path)
+            .unwrap();
+        writeln!(log, "{{\"seq\":99,\"op\":\"insert\"}}").unwrap();
+        drop(log);
+
+        let recovered = RustDb::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            recovered.get_document("users", "1").unwrap()["name"],
+            "Alice"
+        );
+        assert_eq!(
+            recovered.get_document("users", "2").unwrap()["name"],
+            "Bob"
+        );
+
+        // The recovered state must be checkpointed to disk, not just held in
+        // memory, so a second crash before the next write can't lose it.
+        assert!(fs::read_to_string(&recovered.log_path).unwrap().is_empty());
+        let on_disk = fs::read_to_string(&recovered.file_path).unwrap();
+        assert!(on_disk.contains("Bob"));
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn insert_document_rejects_duplicate_unique_field() {
+        let path = temp_db_path("unique_index");
+        cleanup(&path);
+
+        let mut db = RustDb::new(path.to_str().unwrap()).unwrap();
+        db.create_unique_index("users", "email");
+
+        db.insert_document(
+            "users".to_string(),
+            "1".to_string(),
+            serde_json::json!({"email": "a@example.com"}),
+        )
+        .unwrap();
+
+        let result = db.insert_document(
+            "users".to_string(),
+            "2".to_string(),
+            serde_json::json!({"email": "a@example.com"}),
+        );
+        assert!(matches!(result, Err(RustDbError::NotUnique { .. })));
+        assert!(db.get_document("users", "2").is_none());
+
+        // Re-inserting under the same id (an update) must still be allowed.
+        db.insert_document(
+            "users".to_string(),
+            "1".to_string(),
+            serde_json::json!({"email": "a@example.com"}),
+        )
+        .unwrap();
 
-    pub fn clear(&mut self) {
-        self.data.clear();
+        cleanup(&path);
     }
 }
@@ -1,26 +1,67 @@
 use rustDB::{Document, RustDb, RustDbError};
-use std::io::{self, Write};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+/// Counts unbalanced `{`/`[` in a (possibly partial) JSON document so the
+/// REPL knows whether it needs to keep reading continuation lines before
+/// handing the assembled text to `serde_json::from_str`.
+fn braces_balanced(text: &str) -> bool {
+    let mut depth: i64 = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth <= 0
+}
 
 fn main() {
     let db_file = "my_db.json";
 
     let mut db = RustDb::new(db_file).expect("Failed to initialize database");
 
+    let history_path = format!("{}.history", db_file);
+    let mut rl = DefaultEditor::new().expect("Failed to initialize line editor");
+    let _ = rl.load_history(&history_path);
+
     println!("Welcome to the RustDB CLI");
     println!("Type 'help' for commands.");
 
     loop {
-        print!("db> ");
-        io::stdout().flush().expect("Could not flush stdout");
+        let line = match rl.readline("db> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => {
+                println!("Exiting database CLI");
+                break;
+            }
+            Err(err) => {
+                eprintln!("Error reading input: {:?}", err);
+                break;
+            }
+        };
 
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read line");
-        let input = input.trim();
+        let input = line.trim();
+        if input.is_empty() {
+            continue;
+        }
+        let _ = rl.add_history_entry(input);
 
         let parts: Vec<&str> = input.split_whitespace().collect();
-
         if parts.is_empty() {
             continue;
         }
@@ -28,35 +69,36 @@ fn main() {
         match parts[0] {
             "insert" => {
                 // Usage: insert <collection_name> <document_id> <json_document>
-                // Example: insert users user1 '{"name": "Alice", "age": 30}'
+                // Example: insert users user1 {"name": "Alice", "age": 30}
                 if parts.len() >= 4 {
                     let collection_name = parts[1].to_string();
                     let document_id = parts[2].to_string();
 
-                    // Find the start of the JSON document
-                    // This assumes the JSON document starts after the first 3 parts (command, collection, id)
-                    let json_start_index = input.find(parts[3]).unwrap_or(0);
-                    let json_str = &input[json_start_index..];
-
-                    // IMPORTANT: If the user typed single quotes around the JSON,
-                    // these quotes might be stripped by the shell before your
-                    // program sees them. We need to handle cases where the JSON
-                    // string itself might contain single quotes within it or
-                    // where the user omitted them.
-
-                    // A robust way to handle this is to trim surrounding quotes if present.
-                    let clean_json_str = if json_str.starts_with('\'') && json_str.ends_with('\'') {
-                        // If it's explicitly quoted with single quotes
-                        &json_str[1..json_str.len() - 1]
-                    } else if json_str.starts_with('"') && json_str.ends_with('"') {
-                        // If it's explicitly quoted with double quotes
-                        &json_str[1..json_str.len() - 1]
-                    } else {
-                        // Assume no outer quotes, or they were stripped by the shell
-                        json_str
-                    };
+                    // Split on the first 3 fields positionally rather than
+                    // searching for `parts[3]`'s text, since a text search
+                    // can match the id or collection name instead of the
+                    // actual JSON (e.g. an id of "5" appearing inside a
+                    // later numeric value).
+                    let mut json_str = input
+                        .splitn(4, ' ')
+                        .nth(3)
+                        .unwrap_or_default()
+                        .to_string();
 
-                    match serde_json::from_str::<Document>(clean_json_str) {
+                    // The document may span multiple lines; keep reading
+                    // continuation lines until the braces/brackets balance.
+                    while !braces_balanced(&json_str) {
+                        match rl.readline(".... ") {
+                            Ok(continuation) => {
+                                let _ = rl.add_history_entry(continuation.as_str());
+                                json_str.push('\n');
+                                json_str.push_str(&continuation);
+                            }
+                            Err(_) => break,
+                        }
+                    }
+
+                    match serde_json::from_str::<Document>(&json_str) {
                         Ok(document) => {
                             match db.insert_document(
                                 collection_name.clone(),
@@ -67,15 +109,14 @@ fn main() {
                                     "Inserted document into collection '{}' with ID '{}'",
                                     collection_name, document_id
                                 ),
-                                Err(e) => eprintln!("Error inserting document: {:?}", e),
+                                Err(e) => eprintln!("Error inserting document: {}", e),
                             }
                         }
                         Err(e) => eprintln!("Error parsing JSON document: {:?}", e),
                     }
                 } else {
                     println!("Usage: insert <collection_name> <document_id> <json_document>");
-                    println!("Example: insert users user1 '{{\"name\": \"Alice\", \"age\": 30}}'");
-                    println!("Or:      insert users user1 {{\"name\": \"Alice\", \"age\": 30}}");
+                    println!("Example: insert users user1 {{\"name\": \"Alice\", \"age\": 30}}");
                 }
             }
             "get" => {
@@ -86,9 +127,7 @@ fn main() {
                         Some(document) => {
                             println!(
                                 "Document in '{}' with ID '{}': {}",
-                                collection_name,
-                                document_id,
-                                document.to_string()
+                                collection_name, document_id, document
                             );
                         }
                         None => println!(
@@ -114,7 +153,7 @@ fn main() {
                             "Document with ID: '{}' not found in collection: '{}'",
                             document_id, collection_name
                         ),
-                        Err(e) => eprintln!("Error deleting: {:?}", e),
+                        Err(e) => eprintln!("Error deleting: {}", e),
                     }
                 } else {
                     println!("Usage: delete <collection_name> <document_id>");
@@ -130,7 +169,7 @@ fn main() {
                     } else {
                         println!("{{");
                         for (id, doc) in data {
-                            println!("  {}:\n      {}", id, doc.to_string());
+                            println!("  {}:\n      {}", id, doc);
                         }
                         println!("}}")
                     }
@@ -138,6 +177,80 @@ fn main() {
                     println!("Usage: list <collection_name>");
                 }
             }
+            "find" => {
+                // Usage: find <collection_name> <field> <predicate>
+                // Example: find users age 30
+                // Example: find users age >25
+                // Example: find users name ~^A
+                if parts.len() >= 4 {
+                    let collection_name = parts[1];
+                    let field_path = parts[2];
+                    let predicate = parts[3..].join(" ");
+
+                    match db.find(collection_name, field_path, &predicate) {
+                        Ok(matches) => {
+                            if matches.is_empty() {
+                                println!(
+                                    "No documents in '{}' matched {} {}",
+                                    collection_name, field_path, predicate
+                                );
+                            } else {
+                                println!("{{");
+                                for (id, doc) in matches {
+                                    println!("  {}:\n      {}", id, doc);
+                                }
+                                println!("}}")
+                            }
+                        }
+                        Err(e) => eprintln!("Error finding documents: {}", e),
+                    }
+                } else {
+                    println!("Usage: find <collection_name> <field> <predicate>");
+                }
+            }
+            "import" => {
+                // Usage: import <collection_name> <file> [--id-field <field>]
+                // Example: import users users.json
+                // Example: import users users.csv --id-field email
+                if parts.len() >= 3 {
+                    let collection_name = parts[1];
+                    let file_path = parts[2];
+                    let id_field = if parts.len() >= 5 && parts[3] == "--id-field" {
+                        Some(parts[4])
+                    } else {
+                        None
+                    };
+
+                    match db.import_collection(collection_name, file_path, id_field) {
+                        Ok(count) => println!(
+                            "Imported {} document(s) into collection '{}' from '{}'",
+                            count, collection_name, file_path
+                        ),
+                        Err(e) => eprintln!("Error importing '{}': {}", file_path, e),
+                    }
+                } else {
+                    println!("Usage: import <collection_name> <file> [--id-field <field>]");
+                }
+            }
+            "export" => {
+                // Usage: export <collection_name> <file>
+                // Example: export users users.json
+                // Example: export users users.csv
+                if parts.len() == 3 {
+                    let collection_name = parts[1];
+                    let file_path = parts[2];
+
+                    match db.export_collection(collection_name, file_path) {
+                        Ok(count) => println!(
+                            "Exported {} document(s) from collection '{}' to '{}'",
+                            count, collection_name, file_path
+                        ),
+                        Err(e) => eprintln!("Error exporting to '{}': {}", file_path, e),
+                    }
+                } else {
+                    println!("Usage: export <collection_name> <file>");
+                }
+            }
             "clear" => {
                 if parts.len() == 2 {
                     let collection_name = parts[1];
@@ -153,7 +266,7 @@ fn main() {
             "help" => {
                 println!("Commands:");
                 println!("  insert <collection> <doc_id> <json_doc> - Inserts/updates a document.");
-                println!("    Example: insert users user1 '{{\"name\":\"Alice\",\"age\":30}}'");
+                println!("    Example: insert users user1 {{\"name\":\"Alice\",\"age\":30}}");
                 println!("  get <collection> <doc_id>           - Retrieves a document.");
                 println!("  delete <collection> <doc_id>        - Deletes a document.");
                 println!(
@@ -163,6 +276,14 @@ fn main() {
                     "  find <collection> <field> <json_val> - Finds documents by field value."
                 );
                 println!("    Example: find users age 30");
+                println!(
+                    "  import <collection> <file> [--id-field <f>] - Imports a JSON/CSV file."
+                );
+                println!("    Example: import users users.csv --id-field email");
+                println!(
+                    "  export <collection> <file>          - Exports a collection to JSON/CSV."
+                );
+                println!("    Example: export users users.csv");
                 println!(
                     "  clearall                            - Clears all collections and documents."
                 );
@@ -178,4 +299,8 @@ fn main() {
             }
         }
     }
+
+    if let Err(e) = rl.save_history(&history_path) {
+        eprintln!("Could not save command history: {:?}", e);
+    }
 }
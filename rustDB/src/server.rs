@@ -0,0 +1,85 @@
+//! Optional HTTP front-end for `RustDb`, enabled by the `server` feature.
+//!
+//! Exposes the store as a tiny embedded document service over REST instead
+//! of only the interactive CLI, reusing the same persistence/WAL logic as
+//! `RustDb`'s normal methods.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use tokio::sync::Mutex;
+
+use crate::{Document, RustDb, RustDbError};
+
+type SharedDb = Arc<Mutex<RustDb>>;
+
+/// Maps a `RustDbError` to the HTTP status the REST API should report.
+impl IntoResponse for RustDbError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            RustDbError::KeyNotFound => StatusCode::NOT_FOUND,
+            RustDbError::SerializationError(_) | RustDbError::DeserializationError(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            RustDbError::RegexError(_) | RustDbError::CsvError(_) => StatusCode::BAD_REQUEST,
+            RustDbError::NotUnique { .. } => StatusCode::CONFLICT,
+            RustDbError::IoError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, format!("{:?}", self)).into_response()
+    }
+}
+
+async fn insert(
+    State(db): State<SharedDb>,
+    Path((collection, id)): Path<(String, String)>,
+    Json(document): Json<Document>,
+) -> Result<StatusCode, RustDbError> {
+    db.lock().await.insert_document(collection, id, document)?;
+    Ok(StatusCode::OK)
+}
+
+async fn get_handler(
+    State(db): State<SharedDb>,
+    Path((collection, id)): Path<(String, String)>,
+) -> Result<Json<Document>, RustDbError> {
+    match db.lock().await.get_document(&collection, &id) {
+        Some(document) => Ok(Json(document.clone())),
+        None => Err(RustDbError::KeyNotFound),
+    }
+}
+
+async fn delete_handler(
+    State(db): State<SharedDb>,
+    Path((collection, id)): Path<(String, String)>,
+) -> Result<StatusCode, RustDbError> {
+    db.lock().await.delete_document(&collection, &id)?;
+    Ok(StatusCode::OK)
+}
+
+async fn list_handler(
+    State(db): State<SharedDb>,
+    Path(collection): Path<String>,
+) -> Json<Vec<(String, Document)>> {
+    Json(db.lock().await.list_collection_documents(&collection))
+}
+
+fn router(db: RustDb) -> Router {
+    let db: SharedDb = Arc::new(Mutex::new(db));
+    Router::new()
+        .route("/:collection", get(list_handler))
+        .route(
+            "/:collection/:id",
+            post(insert).get(get_handler).delete(delete_handler),
+        )
+        .with_state(db)
+}
+
+/// Serves `db` over HTTP at `addr`, e.g. `serve(db, "127.0.0.1:3000")`.
+pub async fn serve(db: RustDb, addr: &str) -> Result<(), std::io::Error> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router(db)).await
+}